@@ -0,0 +1,19 @@
+mod strategy;
+
+pub use strategy::{
+    CachingOutcome, ObservedCacheStrategy, StatementCacheObserver, StatementCacheStrategy,
+    WithCacheStrategy, WithLruCacheStrategy, WithPredicateCacheStrategy, WithoutCacheStrategy,
+};
+
+/// Describes how many statements a [`StatementCacheStrategy`] keeps around.
+#[allow(unreachable_pub)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheSize {
+    /// Cache as many statements as necessary.
+    Unbounded,
+    /// Never cache any statement.
+    Disabled,
+    /// Cache at most this many statements, evicting the least-recently-used
+    /// one once that limit would be exceeded.
+    Bounded(usize),
+}
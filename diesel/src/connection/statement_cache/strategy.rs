@@ -27,6 +27,108 @@ where
         source: &dyn QueryFragmentForCachedStatement<DB>,
         prepare_fn: &mut dyn FnMut(&str, PrepareForCache) -> Result<Statement, Error>,
     ) -> Result<MaybeCached<'_, Statement>, Error>;
+
+    /// Drop `key` from the cache, if present, so that the next `get` for it
+    /// re-prepares the statement from scratch.
+    ///
+    /// This is the building block for recovering from a recoverable
+    /// server-side invalidation error (for example PostgreSQL's `0A000
+    /// "cached plan must not change result type"` after a schema change):
+    /// pair it with [`retry_once_after_invalidate`] around the execution of
+    /// a cached statement. No connection in this crate calls it yet. The
+    /// default implementation does nothing, which is correct for strategies
+    /// (like [`WithoutCacheStrategy`]) that never keep a `Statement` around
+    /// in the first place.
+    fn invalidate(&mut self, _key: &StatementCacheKey<DB>) {}
+}
+
+/// Runs `execute` once; if it fails with an error that `is_recoverable`
+/// accepts, calls `invalidate` and retries `execute` exactly once more
+/// before giving up.
+///
+/// Intended to be wrapped around a cached statement's execution: pass
+/// `|| strategy.invalidate(&key)` as `invalidate` so a recoverable
+/// server-side invalidation error re-prepares the statement instead of
+/// leaving a poisoned entry cached or retrying forever.
+#[allow(unreachable_pub)]
+pub fn retry_once_after_invalidate<T, E>(
+    mut execute: impl FnMut() -> Result<T, E>,
+    is_recoverable: impl FnOnce(&E) -> bool,
+    invalidate: impl FnOnce(),
+) -> Result<T, E> {
+    match execute() {
+        Err(e) if is_recoverable(&e) => {
+            invalidate();
+            execute()
+        }
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod retry_once_after_invalidate_tests {
+    use std::cell::Cell;
+
+    use super::retry_once_after_invalidate;
+
+    #[test]
+    fn retries_once_after_a_recoverable_error() {
+        let attempts = Cell::new(0);
+        let invalidated = Cell::new(false);
+
+        let result = retry_once_after_invalidate(
+            || {
+                attempts.set(attempts.get() + 1);
+                if attempts.get() == 1 {
+                    Err("stale plan")
+                } else {
+                    Ok("fresh statement")
+                }
+            },
+            |_| true,
+            || invalidated.set(true),
+        );
+
+        assert_eq!(result, Ok("fresh statement"));
+        assert_eq!(attempts.get(), 2);
+        assert!(invalidated.get());
+    }
+
+    #[test]
+    fn does_not_retry_a_second_failure() {
+        let attempts = Cell::new(0);
+
+        let result = retry_once_after_invalidate(
+            || {
+                attempts.set(attempts.get() + 1);
+                Err::<(), _>("stale plan")
+            },
+            |_| true,
+            || {},
+        );
+
+        assert_eq!(result, Err("stale plan"));
+        assert_eq!(attempts.get(), 2);
+    }
+
+    #[test]
+    fn does_not_invalidate_or_retry_a_non_recoverable_error() {
+        let attempts = Cell::new(0);
+        let invalidated = Cell::new(false);
+
+        let result = retry_once_after_invalidate(
+            || {
+                attempts.set(attempts.get() + 1);
+                Err::<(), _>("syntax error")
+            },
+            |_| false,
+            || invalidated.set(true),
+        );
+
+        assert_eq!(result, Err("syntax error"));
+        assert_eq!(attempts.get(), 1);
+        assert!(!invalidated.get());
+    }
 }
 
 /// Cache all (safe) statements for as long as connection is alive.
@@ -77,6 +179,10 @@ where
     fn strategy(&self) -> CacheSize {
         CacheSize::Unbounded
     }
+
+    fn invalidate(&mut self, key: &StatementCacheKey<DB>) {
+        self.cache.remove(key);
+    }
 }
 
 /// No statements will be cached,
@@ -110,6 +216,351 @@ where
     }
 }
 
+/// Cache at most `capacity` statements, evicting the least-recently-used
+/// entry once that limit would be exceeded. A capacity of `0` behaves like
+/// [`WithoutCacheStrategy`].
+#[allow(missing_debug_implementations, unreachable_pub)]
+pub struct WithLruCacheStrategy<DB, Statement>
+where
+    DB: Backend,
+{
+    cache: HashMap<StatementCacheKey<DB>, LruEntry<Statement>>,
+    capacity: usize,
+    clock: u64,
+}
+
+struct LruEntry<Statement> {
+    statement: Statement,
+    last_used: u64,
+}
+
+impl<DB, Statement> WithLruCacheStrategy<DB, Statement>
+where
+    DB: Backend,
+{
+    /// Construct a new strategy that caches at most `capacity` statements.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            cache: Default::default(),
+            capacity,
+            clock: 0,
+        }
+    }
+
+    /// Remove the least-recently-used entry, making room for one more
+    /// statement. Called before inserting so that the new entry's `&mut`
+    /// borrow of `self.cache` never needs to coexist with this lookup.
+    fn evict_lru(&mut self) {
+        let lru_key = least_recently_used_key(self.cache.iter().map(|(k, e)| (k, e.last_used)));
+        if let Some(key) = lru_key {
+            self.cache.remove(&key);
+        }
+    }
+}
+
+/// Pick the key with the smallest `last_used` value, if any.
+///
+/// Kept separate from [`WithLruCacheStrategy`] so the eviction-selection
+/// logic can be unit tested without a real [`Backend`].
+fn least_recently_used_key<'a, K>(usage: impl Iterator<Item = (&'a K, u64)>) -> Option<K>
+where
+    K: Clone + 'a,
+{
+    usage.min_by_key(|(_, last_used)| *last_used).map(|(k, _)| k.clone())
+}
+
+#[cfg(test)]
+mod lru_eviction_tests {
+    use super::least_recently_used_key;
+
+    #[test]
+    fn picks_the_smallest_last_used_value() {
+        let usage = [("a", 3u64), ("b", 1u64), ("c", 2u64)];
+        let picked = least_recently_used_key(usage.iter().map(|(k, v)| (k, *v)));
+        assert_eq!(picked, Some("b"));
+    }
+
+    #[test]
+    fn returns_none_for_empty_usage() {
+        let usage: [(&str, u64); 0] = [];
+        let picked = least_recently_used_key(usage.iter().map(|(k, v)| (k, *v)));
+        assert_eq!(picked, None);
+    }
+}
+
+impl<DB, Statement> StatementCacheStrategy<DB, Statement> for WithLruCacheStrategy<DB, Statement>
+where
+    DB: Backend,
+    StatementCacheKey<DB>: Hash + Eq + Clone,
+    DB::TypeMetadata: Clone,
+    DB::QueryBuilder: Default,
+{
+    fn get(
+        &mut self,
+        key: StatementCacheKey<DB>,
+        backend: &DB,
+        source: &dyn QueryFragmentForCachedStatement<DB>,
+        prepare_fn: &mut dyn FnMut(&str, PrepareForCache) -> Result<Statement, Error>,
+    ) -> Result<MaybeCached<'_, Statement>, Error> {
+        if self.capacity == 0 {
+            let sql = key.sql(source, backend)?;
+            return Ok(MaybeCached::CannotCache(prepare_fn(
+                &sql,
+                PrepareForCache::No,
+            )?));
+        }
+
+        self.clock += 1;
+        let now = self.clock;
+
+        if self.cache.contains_key(&key) {
+            let entry = self.cache.get_mut(&key).expect("key is present");
+            entry.last_used = now;
+            return Ok(MaybeCached::Cached(&mut entry.statement));
+        }
+
+        let sql = key.sql(source, backend)?;
+        let statement = prepare_fn(&sql, PrepareForCache::Yes)?;
+
+        if self.cache.len() >= self.capacity {
+            self.evict_lru();
+        }
+
+        let entry = self.cache.entry(key).or_insert(LruEntry {
+            statement,
+            last_used: now,
+        });
+        Ok(MaybeCached::Cached(&mut entry.statement))
+    }
+
+    fn strategy(&self) -> CacheSize {
+        CacheSize::Bounded(self.capacity)
+    }
+
+    fn invalidate(&mut self, key: &StatementCacheKey<DB>) {
+        self.cache.remove(key);
+    }
+}
+
+/// Outcome of a call to [`StatementCacheStrategy::get`].
+#[allow(unreachable_pub)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachingOutcome {
+    /// Statement was taken from cache
+    UseCached,
+    /// Statement was put to cache
+    Cache,
+    /// Statement wasn't cached
+    DontCache,
+}
+
+/// Receives the outcome of each [`StatementCacheStrategy::get`] call made
+/// through an [`ObservedCacheStrategy`].
+///
+/// A blanket implementation for `Fn(&str, CachingOutcome)` is provided, so
+/// most callers can just pass a closure instead of implementing this trait.
+#[allow(unreachable_pub)]
+pub trait StatementCacheObserver {
+    /// Called once per successful `get`, with the rendered SQL and the outcome
+    /// that the wrapped strategy decided for it. A `get` that returns an error
+    /// is not reported.
+    fn observe(&self, sql: &str, outcome: CachingOutcome);
+}
+
+impl<F> StatementCacheObserver for F
+where
+    F: Fn(&str, CachingOutcome),
+{
+    fn observe(&self, sql: &str, outcome: CachingOutcome) {
+        self(sql, outcome)
+    }
+}
+
+#[cfg(test)]
+mod observer_tests {
+    use std::cell::RefCell;
+
+    use super::{CachingOutcome, StatementCacheObserver};
+
+    #[test]
+    fn closures_implement_the_observer_trait() {
+        let calls = RefCell::new(Vec::new());
+        let observer = |sql: &str, outcome: CachingOutcome| {
+            calls.borrow_mut().push((sql.to_string(), outcome));
+        };
+
+        observer.observe("select 1", CachingOutcome::Cache);
+        observer.observe("select 1", CachingOutcome::UseCached);
+
+        assert_eq!(
+            calls.into_inner(),
+            vec![
+                ("select 1".to_string(), CachingOutcome::Cache),
+                ("select 1".to_string(), CachingOutcome::UseCached),
+            ]
+        );
+    }
+}
+
+/// Wraps a [`StatementCacheStrategy`] and reports each `get` outcome to an
+/// optional [`StatementCacheObserver`].
+#[allow(missing_debug_implementations, unreachable_pub)]
+pub struct ObservedCacheStrategy<DB, Statement>
+where
+    DB: Backend,
+{
+    inner: Box<dyn StatementCacheStrategy<DB, Statement>>,
+    observer: Option<Box<dyn StatementCacheObserver>>,
+}
+
+impl<DB, Statement> ObservedCacheStrategy<DB, Statement>
+where
+    DB: Backend,
+{
+    /// Wrap `strategy` without an observer installed.
+    pub fn new<Strategy>(strategy: Strategy) -> Self
+    where
+        Strategy: StatementCacheStrategy<DB, Statement> + 'static,
+    {
+        ObservedCacheStrategy {
+            inner: Box::new(strategy),
+            observer: None,
+        }
+    }
+
+    /// Wrap `strategy`, reporting every `get` outcome to `observer`.
+    pub fn with_observer<Strategy, Observer>(strategy: Strategy, observer: Observer) -> Self
+    where
+        Strategy: StatementCacheStrategy<DB, Statement> + 'static,
+        Observer: StatementCacheObserver + 'static,
+    {
+        ObservedCacheStrategy {
+            inner: Box::new(strategy),
+            observer: Some(Box::new(observer)),
+        }
+    }
+
+    /// Install or replace the observer.
+    pub fn set_observer<Observer>(&mut self, observer: Observer)
+    where
+        Observer: StatementCacheObserver + 'static,
+    {
+        self.observer = Some(Box::new(observer));
+    }
+}
+
+impl<DB, Statement> StatementCacheStrategy<DB, Statement> for ObservedCacheStrategy<DB, Statement>
+where
+    DB: Backend,
+    StatementCacheKey<DB>: Hash + Eq,
+    DB::TypeMetadata: Clone,
+    DB::QueryBuilder: Default,
+{
+    fn get(
+        &mut self,
+        key: StatementCacheKey<DB>,
+        backend: &DB,
+        source: &dyn QueryFragmentForCachedStatement<DB>,
+        prepare_fn: &mut dyn FnMut(&str, PrepareForCache) -> Result<Statement, Error>,
+    ) -> Result<MaybeCached<'_, Statement>, Error> {
+        // No observer installed: forward directly, without rendering SQL or
+        // allocating anything on top of what `inner` already does.
+        let Some(observer) = self.observer.as_deref() else {
+            return self.inner.get(key, backend, source, prepare_fn);
+        };
+
+        let mut outcome = None;
+
+        let sql = key.sql(source, backend)?;
+        let res = self
+            .inner
+            .get(key, backend, source, &mut |sql, is_cached| {
+                outcome = Some(match is_cached {
+                    PrepareForCache::Yes => CachingOutcome::Cache,
+                    PrepareForCache::No => CachingOutcome::DontCache,
+                });
+                prepare_fn(sql, is_cached)
+            })?;
+        observer.observe(&sql, outcome.unwrap_or(CachingOutcome::UseCached));
+        Ok(res)
+    }
+
+    fn strategy(&self) -> CacheSize {
+        self.inner.strategy()
+    }
+
+    fn invalidate(&mut self, key: &StatementCacheKey<DB>) {
+        self.inner.invalidate(key)
+    }
+}
+
+/// Cache statements, except for the ones that `should_cache` rejects.
+#[allow(missing_debug_implementations, unreachable_pub)]
+pub struct WithPredicateCacheStrategy<DB, Statement, Predicate>
+where
+    DB: Backend,
+{
+    cache: HashMap<StatementCacheKey<DB>, Statement>,
+    should_cache: Predicate,
+}
+
+impl<DB, Statement, Predicate> WithPredicateCacheStrategy<DB, Statement, Predicate>
+where
+    DB: Backend,
+    Predicate: Fn(&StatementCacheKey<DB>, &str) -> bool,
+{
+    /// Construct a new strategy that only caches statements for which
+    /// `should_cache` returns `true`.
+    pub fn new(should_cache: Predicate) -> Self {
+        Self {
+            cache: Default::default(),
+            should_cache,
+        }
+    }
+}
+
+impl<DB, Statement, Predicate> StatementCacheStrategy<DB, Statement>
+    for WithPredicateCacheStrategy<DB, Statement, Predicate>
+where
+    DB: Backend,
+    StatementCacheKey<DB>: Hash + Eq,
+    DB::TypeMetadata: Clone,
+    DB::QueryBuilder: Default,
+    Predicate: Fn(&StatementCacheKey<DB>, &str) -> bool,
+{
+    fn get(
+        &mut self,
+        key: StatementCacheKey<DB>,
+        backend: &DB,
+        source: &dyn QueryFragmentForCachedStatement<DB>,
+        prepare_fn: &mut dyn FnMut(&str, PrepareForCache) -> Result<Statement, Error>,
+    ) -> Result<MaybeCached<'_, Statement>, Error> {
+        if self.cache.contains_key(&key) {
+            let stmt = self.cache.get_mut(&key).expect("key is present");
+            return Ok(MaybeCached::Cached(stmt));
+        }
+
+        let sql = key.sql(source, backend)?;
+        if (self.should_cache)(&key, &sql) {
+            let st = prepare_fn(&sql, PrepareForCache::Yes)?;
+            Ok(MaybeCached::Cached(self.cache.entry(key).or_insert(st)))
+        } else {
+            Ok(MaybeCached::CannotCache(prepare_fn(
+                &sql,
+                PrepareForCache::No,
+            )?))
+        }
+    }
+
+    fn strategy(&self) -> CacheSize {
+        CacheSize::Unbounded
+    }
+
+    fn invalidate(&mut self, key: &StatementCacheKey<DB>) {
+        self.cache.remove(key);
+    }
+}
+
 /// Utilities that help to introspect statement caching behaviour in tests.
 #[allow(dead_code)]
 #[cfg(test)]
@@ -151,17 +602,6 @@ pub mod testing_utils {
         }
     }
 
-    /// Outcome of call to [`StatementCacheStrategy::get`] implementation.
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    pub enum CachingOutcome {
-        /// Statement was taken from cache
-        UseCached,
-        /// Statement was put to cache
-        Cache,
-        /// Statement wasn't cached
-        DontCache,
-    }
-
     /// Result summary of call to [`StatementCacheStrategy::get`]
     #[derive(Debug, PartialEq, Eq)]
     pub struct CallInfo {
@@ -238,5 +678,9 @@ pub mod testing_utils {
         fn strategy(&self) -> CacheSize {
             self.inner.strategy()
         }
+
+        fn invalidate(&mut self, key: &StatementCacheKey<DB>) {
+            self.inner.invalidate(key)
+        }
     }
 }